@@ -0,0 +1,181 @@
+//! Measures the gas used by deploying the contract and by its main operations on a local node,
+//! and compares it against the baseline in `benches/gas_baseline.json`, failing when it is
+//! missing or gas regressed.
+//!
+//! Run with `cargo bench --bench gas`. Set `UPDATE_GAS_BASELINE=1` to record the baseline, then
+//! commit it; do the same after an intentional change.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::*;
+use fuels::{
+    client::{PageDirection, PaginationRequest},
+    prelude::*,
+    tx::Bytes32,
+    types::{transaction_response::TransactionResponse, Bits256},
+};
+use serde_json::{Map, Value};
+use std::{env, fs, path::Path, process};
+
+const BASELINE_PATH: &str = "./benches/gas_baseline.json";
+
+/// Maximum allowed increase over the baseline, in percent.
+const REGRESSION_THRESHOLD: f64 = 5.0;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let measurements = vec![
+        ("deploy", measure_deploy().await),
+        ("mint", measure_mint().await),
+        ("mint_multicall_10", measure_mint_multicall().await),
+        ("burn", measure_burn().await),
+    ];
+
+    if env::var("UPDATE_GAS_BASELINE").is_ok() {
+        write_baseline(&measurements);
+        println!("recorded gas baseline in {BASELINE_PATH}");
+        return;
+    }
+
+    let Some(baseline) = load_baseline() else {
+        eprintln!(
+            "no gas baseline in {BASELINE_PATH}, record one with `UPDATE_GAS_BASELINE=1 cargo \
+             bench --bench gas` and commit it"
+        );
+        process::exit(1);
+    };
+
+    let mut regressed = false;
+    for (name, gas_used) in &measurements {
+        match baseline.get(*name).and_then(Value::as_u64) {
+            Some(expected) => {
+                let change = (*gas_used as f64 - expected as f64) / expected as f64 * 100.0;
+                println!("{name}: {gas_used} gas (baseline {expected}, {change:+.2}%)");
+                if change > REGRESSION_THRESHOLD {
+                    regressed = true;
+                }
+            }
+            None => {
+                eprintln!("{name}: {gas_used} gas (missing from {BASELINE_PATH})");
+                regressed = true;
+            }
+        }
+    }
+
+    if regressed {
+        eprintln!(
+            "gas usage regressed by more than {REGRESSION_THRESHOLD}% or is missing from the \
+             baseline"
+        );
+        process::exit(1);
+    }
+}
+
+async fn measure_deploy() -> u64 {
+    // A dedicated node, so that the deploy is the only transaction of the wallet
+    let wallet = launch_provider_and_get_wallet().await.unwrap();
+    let provider = wallet.try_provider().unwrap();
+
+    Contract::load_from(CONTRACT_BINARY, LoadConfiguration::default())
+        .unwrap()
+        .deploy(&wallet, TxPolicies::default())
+        .await
+        .unwrap();
+
+    let transactions = provider
+        .get_transactions_by_owner(
+            wallet.address(),
+            PaginationRequest {
+                cursor: None,
+                results: 2,
+                direction: PageDirection::Backward,
+            },
+        )
+        .await
+        .unwrap()
+        .results;
+    let [TransactionResponse {
+        transaction: TransactionType::Create(tx),
+        ..
+    }] = transactions.as_slice()
+    else {
+        panic!("expected the deploy to be the only transaction of the wallet: {transactions:?}");
+    };
+
+    // A create transaction runs no script, so it is charged its minimum gas: the gas for its
+    // size, its inputs and the storage slots it initializes
+    tx.fee_checked_from_tx(provider.consensus_parameters())
+        .unwrap()
+        .min_gas()
+}
+
+async fn measure_mint() -> u64 {
+    let ctx = TestContext::new().await;
+    let recipient = ctx.predicate_identity(ctx.wallets[0].address().into());
+
+    ctx.contract
+        .methods()
+        .mint(recipient, Bits256([1u8; 32]), 1)
+        .call()
+        .await
+        .unwrap()
+        .gas_used
+}
+
+async fn measure_mint_multicall() -> u64 {
+    let ctx = TestContext::new().await;
+    let recipient = ctx.predicate_identity(ctx.wallets[0].address().into());
+
+    let mints = std::array::from_fn(|i| (recipient.clone(), Bytes32::from([i as u8; 32])));
+    ctx.mint_multicall(&mints).await
+}
+
+async fn measure_burn() -> u64 {
+    let ctx = TestContext::new().await;
+    let sub_id = Bytes32::from([1u8; 32]);
+
+    // Mint to the owner directly, as coins in a soulbound predicate can never be forwarded
+    ctx.contract
+        .methods()
+        .mint(ctx.deployer_identity(), Bits256(*sub_id), 1)
+        .call()
+        .await
+        .unwrap();
+
+    ctx.contract
+        .methods()
+        .burn(Bits256(*sub_id), 1)
+        .call_params(
+            CallParameters::default()
+                .with_amount(1)
+                .with_asset_id(ctx.asset_id(sub_id)),
+        )
+        .unwrap()
+        .call()
+        .await
+        .unwrap()
+        .gas_used
+}
+
+fn load_baseline() -> Option<Map<String, Value>> {
+    if !Path::new(BASELINE_PATH).exists() {
+        return None;
+    }
+
+    let contents = fs::read_to_string(BASELINE_PATH).unwrap();
+    match serde_json::from_str(&contents).unwrap() {
+        Value::Object(baseline) => Some(baseline),
+        _ => panic!("{BASELINE_PATH} must contain a JSON object"),
+    }
+}
+
+fn write_baseline(measurements: &[(&str, u64)]) {
+    let baseline: Map<String, Value> = measurements
+        .iter()
+        .map(|(name, gas_used)| (name.to_string(), Value::from(*gas_used)))
+        .collect();
+
+    let contents = serde_json::to_string_pretty(&baseline).unwrap();
+    fs::write(BASELINE_PATH, contents + "\n").unwrap();
+}
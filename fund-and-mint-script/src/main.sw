@@ -0,0 +1,25 @@
+script;
+
+use standards::src3::SRC3;
+use std::{asset::transfer, constants::BASE_ASSET_ID};
+
+/// Mints the NFT with `sub_id` to `recipient` and sends it `base_asset_amount` of the base asset,
+/// atomically in a single transaction.
+///
+/// # Arguments
+///
+/// * `badges_id`: [b256] - The id of the badge contract to mint from.
+/// * `recipient`: [Address] - The address receiving both the NFT and the base asset.
+/// * `sub_id`: [SubId] - The sub-identifier of the NFT to mint.
+/// * `base_asset_amount`: [u64] - The amount of the base asset to send to `recipient`.
+///
+/// # Reverts
+///
+/// * When the transaction is not sent by the owner of the badge contract.
+/// * When the NFT has already been minted.
+fn main(badges_id: b256, recipient: Address, sub_id: SubId, base_asset_amount: u64) {
+    let badges = abi(SRC3, badges_id);
+    badges.mint(Identity::Address(recipient), sub_id, 1);
+
+    transfer(Identity::Address(recipient), BASE_ASSET_ID, base_asset_amount);
+}
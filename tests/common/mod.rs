@@ -0,0 +1,604 @@
+// Not every integration test uses every helper.
+#![allow(dead_code)]
+
+use fuel_crypto::SecretKey;
+use fuels::{
+    accounts::predicate::Predicate,
+    prelude::*,
+    tx::{Bytes32, Receipt},
+    types::{Bits256, ContractId, Identity},
+};
+use sha2::{Digest, Sha256};
+use std::{
+    env, fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Mutex, OnceLock,
+    },
+    thread,
+};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+// Load abi from json
+abigen!(
+    Contract(
+        name = "LW3Badges",
+        abi = "nft-contract/out/debug/fuel-badges-abi.json"
+    ),
+    Predicate(
+        name = "SoulboundPredicate",
+        abi = "soulbound-predicate/out/debug/soulbound-predicate-abi.json"
+    ),
+    Script(
+        name = "FundAndMintScript",
+        abi = "fund-and-mint-script/out/debug/fund-and-mint-script-abi.json"
+    )
+);
+
+pub const PREDICATE_BINARY: &str = "./soulbound-predicate/out/debug/soulbound-predicate.bin";
+pub const CONTRACT_BINARY: &str = "./nft-contract/out/debug/fuel-badges.bin";
+pub const FUND_AND_MINT_SCRIPT_BINARY: &str =
+    "./fund-and-mint-script/out/debug/fund-and-mint-script.bin";
+pub const CONTRACT_STORAGE_SLOTS: &str = "./nft-contract/out/debug/fuel-badges-storage_slots.json";
+
+/// Directory holding the committed snapshots compared by [assert_snapshot].
+pub const SNAPSHOT_DIR: &str = "./tests/snapshots";
+
+/// Number of funded wallets on the shared node of the [WalletPool].
+pub const WALLET_POOL_SIZE: usize = 16;
+
+static WALLET_POOL: OnceLock<WalletPool> = OnceLock::new();
+
+/// Counter used by [unique_salt], starting at one so it never returns the default salt.
+static NEXT_SALT: AtomicU64 = AtomicU64::new(1);
+
+/// Number of mints bundled into a single multicall transaction by [TestContext::mint_multicall].
+pub const MINTS_PER_MULTICALL: usize = 10;
+
+/// A freshly deployed and initialized badge contract on a local node.
+pub struct TestContext {
+    /// The contract instance, connected to the deployer wallet.
+    pub contract: LW3Badges<WalletUnlocked>,
+    pub contract_id: ContractId,
+    /// The wallet that deployed the contract and owns it.
+    pub deployer: WalletUnlocked,
+    /// Additional funded wallets, not including the deployer.
+    pub wallets: Vec<WalletUnlocked>,
+    /// Keeps the wallets reserved when they are leased from the [WalletPool].
+    lease: Option<WalletLease>,
+}
+
+/// Configures the local node and wallets a [TestContext] is built on.
+pub struct TestContextBuilder {
+    wallets: u64,
+    coins_per_wallet: u64,
+    amount_per_coin: u64,
+}
+
+impl Default for TestContextBuilder {
+    fn default() -> Self {
+        Self {
+            wallets: 1,
+            coins_per_wallet: 1,
+            amount_per_coin: 1_000_000_000,
+        }
+    }
+}
+
+impl TestContextBuilder {
+    /// Sets the number of wallets created in addition to the deployer.
+    pub fn wallets(mut self, wallets: u64) -> Self {
+        self.wallets = wallets;
+        self
+    }
+
+    /// Sets the number of coins (UTXOs) each wallet starts with.
+    pub fn coins_per_wallet(mut self, coins_per_wallet: u64) -> Self {
+        self.coins_per_wallet = coins_per_wallet;
+        self
+    }
+
+    /// Sets the amount of the base asset held by each coin.
+    pub fn amount_per_coin(mut self, amount_per_coin: u64) -> Self {
+        self.amount_per_coin = amount_per_coin;
+        self
+    }
+
+    /// Launches a local network, deploys the contract and calls its constructor.
+    pub async fn build(self) -> TestContext {
+        let mut wallets = launch_custom_provider_and_get_wallets(
+            WalletsConfig::new(
+                Some(self.wallets + 1), /* Extra wallets plus the deployer */
+                Some(self.coins_per_wallet),
+                Some(self.amount_per_coin),
+            ),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let deployer = wallets.pop().unwrap();
+
+        let (contract, contract_id) = deploy_contract(&deployer).await;
+
+        TestContext {
+            contract,
+            contract_id,
+            deployer,
+            wallets,
+            lease: None,
+        }
+    }
+}
+
+impl TestContext {
+    /// Builds a context with the default wallet configuration, using wallets leased from the
+    /// shared node of the [WalletPool] so that tests can run in parallel.
+    pub async fn new() -> Self {
+        let lease = WalletPool::get().lease(2).await;
+        let mut wallets = lease.wallets.clone();
+        let deployer = wallets.pop().unwrap();
+
+        // Every contract on the shared node needs its own salt to get a distinct id
+        let (contract, contract_id) = deploy_contract_with_salt(&deployer, unique_salt()).await;
+
+        TestContext {
+            contract,
+            contract_id,
+            deployer,
+            wallets,
+            lease: Some(lease),
+        }
+    }
+
+    /// Returns a builder for a context on a dedicated local node, for tests that need a custom
+    /// wallet configuration.
+    pub fn builder() -> TestContextBuilder {
+        TestContextBuilder::default()
+    }
+
+    /// Returns the identity of the deployer, which is also the contract owner.
+    pub fn deployer_identity(&self) -> Identity {
+        Identity::Address(self.deployer.address().into())
+    }
+
+    /// Returns the soulbound predicate of `owner`, connected to the local node.
+    pub fn predicate(&self, owner: Address) -> Predicate {
+        predicate_for(owner).with_provider(self.deployer.try_provider().unwrap().clone())
+    }
+
+    /// Returns the identity of the soulbound predicate of `owner`.
+    pub fn predicate_identity(&self, owner: Address) -> Identity {
+        Identity::Address(predicate_address(owner))
+    }
+
+    /// Returns the asset id of the NFT minted by this contract with `sub_id`.
+    pub fn asset_id(&self, sub_id: Bytes32) -> AssetId {
+        get_asset_id(sub_id, self.contract_id)
+    }
+
+    /// Mints the NFT with `sub_id` into the soulbound predicate of `owner`, and checks that the
+    /// transaction actually minted it there.
+    pub async fn mint_to_predicate(&self, owner: Address, sub_id: Bytes32) -> AssetId {
+        let response = self
+            .contract
+            .methods()
+            .mint(self.predicate_identity(owner), Bits256(*sub_id), 1)
+            .call()
+            .await
+            .unwrap();
+
+        let asset = self.asset_id(sub_id);
+        assert_mint_receipts(
+            &response.receipts,
+            self.contract_id,
+            sub_id,
+            predicate_address(owner),
+        );
+        assert_eq!(
+            self.predicate(owner)
+                .get_asset_balance(&asset)
+                .await
+                .unwrap(),
+            1,
+            "minted NFT is not held by the predicate"
+        );
+
+        asset
+    }
+
+    /// Mints the NFT with `sub_id` into the soulbound predicate of `owner` and funds the predicate
+    /// with `amount` of the base asset, in a single transaction through the fund-and-mint script.
+    pub async fn fund_and_mint_to_predicate(
+        &self,
+        owner: Address,
+        sub_id: Bytes32,
+        amount: u64,
+    ) -> AssetId {
+        let recipient = predicate_address(owner);
+        let provider = self.deployer.try_provider().unwrap();
+        let script = FundAndMintScript::new(self.deployer.clone(), FUND_AND_MINT_SCRIPT_BINARY);
+
+        let call = script
+            .main(
+                Bits256(*self.contract_id),
+                recipient,
+                Bits256(*sub_id),
+                amount,
+            )
+            // Also registers the contract's log decoder, so its reverts are decoded
+            .with_contracts(&[&self.contract])
+            .append_variable_outputs(2)
+            // Pay a non-zero fee, so that the fee and the transfer share the base asset inputs
+            .with_tx_policies(TxPolicies::default().with_gas_price(1));
+
+        // The maximum fee is reserved out of the inputs before the script runs, so they have to
+        // cover the transferred `amount` on top of it. `call()` only adjusts the inputs for the
+        // fee, so the transaction is built here with `amount` counted as already used.
+        let mut tx_builder = call.transaction_builder().await.unwrap();
+        self.deployer.add_witnessses(&mut tx_builder);
+        self.deployer
+            .adjust_for_fee(&mut tx_builder, amount)
+            .await
+            .unwrap();
+        let tx = tx_builder.build(provider).await.unwrap();
+
+        let tx_id = provider
+            .send_transaction_and_await_commit(tx)
+            .await
+            .unwrap();
+        let receipts = provider
+            .tx_status(&tx_id)
+            .await
+            .unwrap()
+            .take_receipts_checked(Some(&self.contract.log_decoder()))
+            .unwrap();
+        let response = call.get_response(receipts).unwrap();
+
+        assert_mint_receipts(&response.receipts, self.contract_id, sub_id, recipient);
+
+        self.asset_id(sub_id)
+    }
+
+    /// Mints one NFT for each `(recipient, sub_id)` pair in a single multicall transaction and
+    /// returns the gas used.
+    pub async fn mint_multicall(&self, mints: &[(Identity, Bytes32); MINTS_PER_MULTICALL]) -> u64 {
+        let mut multi_call = MultiContractCallHandler::new(self.deployer.clone());
+        for (recipient, sub_id) in mints {
+            multi_call.add_call(self.contract.methods().mint(
+                recipient.clone(),
+                Bits256(**sub_id),
+                1,
+            ));
+        }
+
+        multi_call
+            .call::<((), (), (), (), (), (), (), (), (), ())>()
+            .await
+            .unwrap()
+            .gas_used
+    }
+
+    /// Renders the contract state observable through its ABI for the NFTs with `sub_ids`, in a
+    /// form that does not depend on the contract id or the leased wallets.
+    pub async fn state_snapshot(&self, sub_ids: &[Bytes32], metadata_keys: &[&str]) -> String {
+        let methods = self.contract.methods();
+
+        let owner = match methods.owner().simulate().await.unwrap().value {
+            State::Initialized(owner) if owner == self.deployer_identity() => {
+                "Initialized(deployer)".to_string()
+            }
+            owner => format!("{owner:?}"),
+        };
+        let total_assets = methods.total_assets().simulate().await.unwrap().value;
+
+        let mut snapshot = format!("owner: {owner}\ntotal_assets: {total_assets}\n");
+        for sub_id in sub_ids {
+            let asset = self.asset_id(*sub_id);
+            let total_supply = methods.total_supply(asset).simulate().await.unwrap().value;
+            let name = methods.name(asset).simulate().await.unwrap().value;
+            let symbol = methods.symbol(asset).simulate().await.unwrap().value;
+            let decimals = methods.decimals(asset).simulate().await.unwrap().value;
+
+            snapshot += &format!("\nsub_id: {sub_id}\n");
+            snapshot += &format!("  total_supply: {total_supply:?}\n");
+            snapshot += &format!("  name: {name:?}\n");
+            snapshot += &format!("  symbol: {symbol:?}\n");
+            snapshot += &format!("  decimals: {decimals:?}\n");
+            for key in metadata_keys {
+                let metadata = methods
+                    .metadata(asset, key.to_string())
+                    .simulate()
+                    .await
+                    .unwrap()
+                    .value;
+                snapshot += &format!("  metadata[{key}]: {metadata:?}\n");
+            }
+        }
+
+        snapshot
+    }
+
+    /// Sends `amount` of the base asset from the deployer to the soulbound predicate of `owner`,
+    /// so that spends out of the predicate can cover their fees.
+    pub async fn fund_predicate(&self, owner: Address, amount: u64) {
+        self.deployer
+            .transfer(
+                self.predicate(owner).address(),
+                amount,
+                BASE_ASSET_ID,
+                TxPolicies::default(),
+            )
+            .await
+            .unwrap();
+    }
+}
+
+/// A local node shared by all tests of the process, holding a fixed set of funded wallets that
+/// tests lease while they run.
+pub struct WalletPool {
+    node_url: String,
+    secret_keys: Mutex<Vec<SecretKey>>,
+    permits: Semaphore,
+}
+
+impl WalletPool {
+    /// Returns the pool of this process, launching its node on first use.
+    pub fn get() -> &'static WalletPool {
+        WALLET_POOL.get_or_init(Self::launch)
+    }
+
+    fn launch() -> Self {
+        let secret_keys: Vec<SecretKey> = (0..WALLET_POOL_SIZE)
+            .map(|i| {
+                let mut secret_key = [0u8; 32];
+                secret_key[24..].copy_from_slice(&(i as u64 + 1).to_be_bytes());
+                SecretKey::try_from(secret_key.as_slice()).unwrap()
+            })
+            .collect();
+        let coins = secret_keys
+            .iter()
+            .flat_map(|secret_key| {
+                let wallet = WalletUnlocked::new_from_private_key(*secret_key, None);
+                setup_single_asset_coins(wallet.address(), BASE_ASSET_ID, 1, 1_000_000_000)
+            })
+            .collect();
+
+        // Each test runs on its own runtime which is shut down when the test ends, so the node
+        // gets a runtime of its own that lives as long as the process.
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async move {
+                let provider = setup_test_provider(coins, vec![], None, None)
+                    .await
+                    .unwrap();
+                sender.send(provider.url().to_string()).unwrap();
+                std::future::pending::<()>().await
+            })
+        });
+
+        WalletPool {
+            node_url: receiver.recv().unwrap(),
+            secret_keys: Mutex::new(secret_keys),
+            permits: Semaphore::new(WALLET_POOL_SIZE),
+        }
+    }
+
+    /// Leases `count` wallets, waiting until other tests have returned enough of them.
+    pub async fn lease(&'static self, count: usize) -> WalletLease {
+        assert!(
+            count <= WALLET_POOL_SIZE,
+            "cannot lease more than {WALLET_POOL_SIZE} wallets"
+        );
+
+        let permit = self.permits.acquire_many(count as u32).await.unwrap();
+        let provider = Provider::connect(&self.node_url).await.unwrap();
+        let secret_keys = {
+            let mut available = self.secret_keys.lock().unwrap();
+            let remaining = available.len() - count;
+            available.split_off(remaining)
+        };
+        let wallets = secret_keys
+            .iter()
+            .map(|secret_key| {
+                WalletUnlocked::new_from_private_key(*secret_key, Some(provider.clone()))
+            })
+            .collect();
+
+        WalletLease {
+            pool: self,
+            secret_keys,
+            wallets,
+            _permit: permit,
+        }
+    }
+}
+
+/// Wallets leased from the [WalletPool], returned to it when dropped.
+pub struct WalletLease {
+    pool: &'static WalletPool,
+    secret_keys: Vec<SecretKey>,
+    pub wallets: Vec<WalletUnlocked>,
+    _permit: SemaphorePermit<'static>,
+}
+
+impl Drop for WalletLease {
+    fn drop(&mut self) {
+        self.pool
+            .secret_keys
+            .lock()
+            .unwrap()
+            .append(&mut self.secret_keys);
+    }
+}
+
+/// Asserts that `contents` matches the committed snapshot `name` in [SNAPSHOT_DIR].
+///
+/// The snapshot is recorded instead when `UPDATE_SNAPSHOTS=1` is set, to create it or after an
+/// intentional change. A missing snapshot is a failure otherwise.
+pub fn assert_snapshot(name: &str, contents: &str) {
+    let path = Path::new(SNAPSHOT_DIR).join(format!("{name}.snap"));
+
+    if env::var("UPDATE_SNAPSHOTS").is_ok() {
+        fs::create_dir_all(SNAPSHOT_DIR).unwrap();
+        fs::write(&path, contents).unwrap();
+        return;
+    }
+
+    assert!(
+        path.exists(),
+        "snapshot `{name}` is missing from {SNAPSHOT_DIR}, record it with UPDATE_SNAPSHOTS=1 and \
+         commit it\n--- actual\n{contents}"
+    );
+    let expected = fs::read_to_string(&path).unwrap();
+    assert!(
+        expected == contents,
+        "snapshot `{name}` does not match {}, rerun with UPDATE_SNAPSHOTS=1 if the change is \
+         intended\n--- expected\n{expected}\n--- actual\n{contents}",
+        path.display()
+    );
+}
+
+/// Returns a salt no other call in this process returns.
+pub fn unique_salt() -> [u8; 32] {
+    let mut salt = [0u8; 32];
+    salt[24..].copy_from_slice(&NEXT_SALT.fetch_add(1, Ordering::Relaxed).to_be_bytes());
+    salt
+}
+
+/// Deploys a new instance of the contract and calls its constructor with `wallet` as owner.
+pub async fn deploy_contract(wallet: &WalletUnlocked) -> (LW3Badges<WalletUnlocked>, ContractId) {
+    deploy_contract_with_salt(wallet, [0u8; 32]).await
+}
+
+/// Like [deploy_contract], but with a custom salt so that several instances can be deployed
+/// next to each other.
+pub async fn deploy_contract_with_salt(
+    wallet: &WalletUnlocked,
+    salt: [u8; 32],
+) -> (LW3Badges<WalletUnlocked>, ContractId) {
+    deploy_contract_with_policies(wallet, salt, TxPolicies::default()).await
+}
+
+/// Like [deploy_contract_with_salt], but sends the deploy and constructor transactions with
+/// `tx_policies`, e.g. for networks with a minimum gas price.
+pub async fn deploy_contract_with_policies(
+    wallet: &WalletUnlocked,
+    salt: [u8; 32],
+    tx_policies: TxPolicies,
+) -> (LW3Badges<WalletUnlocked>, ContractId) {
+    let id = Contract::load_from(
+        CONTRACT_BINARY,
+        LoadConfiguration::default().with_salt(salt),
+    )
+    .unwrap()
+    .deploy(wallet, tx_policies)
+    .await
+    .unwrap();
+
+    let instance = LW3Badges::new(id.clone(), wallet.clone());
+
+    // Call the constructor
+    let deployer_identity = Identity::Address(instance.account().address().into());
+    instance
+        .methods()
+        .constructor(deployer_identity)
+        .with_tx_policies(tx_policies)
+        .call()
+        .await
+        .unwrap();
+
+    (instance, id.into())
+}
+
+/// Loads the soulbound predicate configured for `owner`, without a provider.
+pub fn predicate_for(owner: Address) -> Predicate {
+    let configurables = SoulboundPredicateConfigurables::new().with_ADDRESS(owner);
+    Predicate::load_from(PREDICATE_BINARY)
+        .unwrap()
+        .with_configurables(configurables)
+}
+
+pub fn predicate_address(owner: Address) -> Address {
+    predicate_for(owner).address().into()
+}
+
+/// Asserts that `receipts` contain the mint of one NFT with `sub_id` by `contract` and its
+/// transfer to `recipient`, dumping all receipts otherwise.
+pub fn assert_mint_receipts(
+    receipts: &[Receipt],
+    contract: ContractId,
+    sub_id: Bytes32,
+    recipient: Address,
+) {
+    let asset = get_asset_id(sub_id, contract);
+
+    let minted = receipts.iter().any(|receipt| {
+        matches!(
+            receipt,
+            Receipt::Mint { sub_id: id, contract_id, val, .. }
+                if *id == sub_id && *contract_id == contract && *val == 1
+        )
+    });
+    assert!(minted, "no mint of {asset} in receipts: {receipts:#?}");
+
+    let transferred = receipts.iter().any(|receipt| {
+        matches!(
+            receipt,
+            Receipt::TransferOut { id, to, amount, asset_id, .. }
+                if *id == contract && *to == recipient && *amount == 1 && *asset_id == asset
+        )
+    });
+    assert!(
+        transferred,
+        "no transfer of {asset} to {recipient} in receipts: {receipts:#?}"
+    );
+}
+
+/// Asserts that `result` failed because a predicate of the transaction ran and returned false,
+/// with `case` describing the attempted spend.
+///
+/// The node reports the failure through the client as an `IOError`, while a predicate that
+/// already fails local estimation surfaces as a `ValidationError` or `ProviderError`, so any of
+/// those carriers is accepted. The reason has to be `PredicateReturnedNonOne`: the other
+/// verification failures, such as `InvalidOwner` for a coin whose owner is not the root of the
+/// predicate bytecode, mean the predicate never ran.
+pub fn assert_predicate_rejected<T>(result: Result<T>, case: &str) {
+    match result {
+        Err(err @ (Error::IOError(_) | Error::ProviderError(_) | Error::ValidationError(_))) => {
+            let message = err.to_string();
+            assert!(
+                message.contains("PredicateVerificationFailed")
+                    && message.contains("PredicateReturnedNonOne"),
+                "{case}: expected the predicate to return false, got `{message}`"
+            )
+        }
+        Err(err) => panic!("{case}: expected the predicate to return false, got {err:?}"),
+        Ok(_) => panic!("{case}: expected the predicate to return false, but the spend succeeded"),
+    }
+}
+
+/// Asserts that `result` failed because the contract reverted with the logged error `reason`,
+/// e.g. `"NotOwner"` for `AccessError::NotOwner`.
+pub fn assert_revert<T>(result: Result<T>, reason: &str) {
+    match result {
+        Err(Error::RevertTransactionError { reason: actual, .. }) => assert!(
+            actual.contains(reason),
+            "expected revert with `{reason}`, got `{actual}`"
+        ),
+        Err(err) => panic!("expected revert with `{reason}`, got {err:?}"),
+        Ok(_) => panic!("expected revert with `{reason}`, but the call succeeded"),
+    }
+}
+
+pub fn get_asset_id(sub_id: Bytes32, contract: ContractId) -> AssetId {
+    let mut hasher = Sha256::new();
+    hasher.update(*contract);
+    hasher.update(*sub_id);
+    AssetId::new(*Bytes32::from(<[u8; 32]>::from(hasher.finalize())))
+}
@@ -1,88 +1,44 @@
+mod common;
+
+use common::*;
 use fuels::{
-    accounts::predicate::Predicate,
-    core::Configurables,
     prelude::*,
-    tx::Bytes32,
+    tx::{Bytes32, ContractIdExt, Receipt},
     types::{Bits256, ContractId, Identity},
 };
-use sha2::{Digest, Sha256};
+use proptest::{prelude::*, strategy::ValueTree, test_runner::TestRunner};
+use serde_json::Value;
 use std::str::FromStr;
 
-// Load abi from json
-abigen!(
-    Contract(
-        name = "LW3Badges",
-        abi = "nft-contract/out/debug/fuel-badges-abi.json"
-    ),
-    Predicate(
-        name = "SoulboundPredicate",
-        abi = "soulbound-predicate/out/debug/soulbound-predicate-abi.json"
-    )
-);
-
-const PREDICATE_BINARY: &str = "./soulbound-predicate/out/debug/soulbound-predicate.bin";
-const CONTRACT_BINARY: &str = "./nft-contract/out/debug/fuel-badges.bin";
-
-async fn get_contract_instance() -> (LW3Badges<WalletUnlocked>, ContractId, Vec<WalletUnlocked>) {
-    // Launch a local network and deploy the contract
-    let mut wallets = launch_custom_provider_and_get_wallets(
-        WalletsConfig::new(
-            Some(2),             /* Two wallets */
-            Some(1),             /* Single coin (UTXO) */
-            Some(1_000_000_000), /* Amount per coin */
-        ),
-        None,
-        None,
-    )
-    .await
-    .unwrap();
-    let wallet = wallets.pop().unwrap();
-
-    let id = Contract::load_from(CONTRACT_BINARY, LoadConfiguration::default())
-        .unwrap()
-        .deploy(&wallet, TxPolicies::default())
-        .await
-        .unwrap();
-
-    let instance = LW3Badges::new(id.clone(), wallet);
-
-    // Call the constructor
-    let deployer_identity = Identity::Address(instance.account().address().into());
-    instance
-        .methods()
-        .constructor(deployer_identity)
-        .call()
-        .await
-        .unwrap();
-
-    (instance, id.into(), wallets)
-}
-
-async fn calculate_predicate_address(addr: Address) -> Address {
-    let configurables = SoulboundPredicateConfigurables::new().with_ADDRESS(addr);
-    let predicate = Predicate::load_from(PREDICATE_BINARY)
-        .unwrap()
-        .with_configurables(configurables);
+/// Derivation results recorded by `test_testnet_record_derivation_vectors` in `tests/testnet.rs`.
+///
+/// The asset ids are taken from the mint receipts of a testnet deployment. The predicate
+/// addresses are computed from the local build, so they are regression vectors for the predicate
+/// bytecode and the configurables encoding, not values read from the chain.
+const DERIVATION_VECTORS: &str = include_str!("fixtures/derivation_vectors.json");
 
-    predicate.address().into()
-}
-
-fn get_asset_id(sub_id: Bytes32, contract: ContractId) -> AssetId {
-    let mut hasher = Sha256::new();
-    hasher.update(*contract);
-    hasher.update(*sub_id);
-    AssetId::new(*Bytes32::from(<[u8; 32]>::from(hasher.finalize())))
+/// Returns the golden vectors of `kind`, failing when none have been recorded.
+fn golden_vectors(kind: &str) -> Vec<Value> {
+    let vectors: Value = serde_json::from_str(DERIVATION_VECTORS).unwrap();
+    let vectors = vectors[kind].as_array().unwrap().clone();
+    assert!(
+        !vectors.is_empty(),
+        "no `{kind}` vectors in tests/fixtures/derivation_vectors.json, record them with \
+         `cargo test --features testnet-tests --test testnet_tests -- --ignored \
+         test_testnet_record_derivation_vectors`"
+    );
+    vectors
 }
 
 #[tokio::test]
 async fn test_sanity() {
-    let (contract, contract_id, mut wallets) = get_contract_instance().await;
+    let mut ctx = TestContext::new().await;
+    let contract = &ctx.contract;
+    let contract_id = ctx.contract_id;
 
-    let deployer_wallet = contract.account();
-    let deployer_identity = Identity::Address(deployer_wallet.address().into());
-    let recipient_wallet = wallets.pop().unwrap();
-    let recipient_predicate = calculate_predicate_address(recipient_wallet.address().into()).await;
-    let recipient_predicate_identity = Identity::Address(recipient_predicate.into());
+    let deployer_identity = ctx.deployer_identity();
+    let recipient_wallet = ctx.wallets.pop().unwrap();
+    let recipient_predicate_identity = ctx.predicate_identity(recipient_wallet.address().into());
     // Sanity Checks
     assert_eq!(contract_id, contract.contract_id().into());
     assert_eq!(
@@ -101,15 +57,9 @@ async fn test_sanity() {
     );
 
     let sub_id_1 = Bytes32::from([1u8; 32]);
-    let sub_id_2 = Bytes32::from([2u8; 32]);
-    let sub_id_3 = Bytes32::from([3u8; 32]);
-    let asset1 = get_asset_id(sub_id_1, contract_id);
-    let asset2 = get_asset_id(sub_id_2, contract_id);
-    let asset3 = get_asset_id(sub_id_3, contract_id);
+    let asset1 = ctx.asset_id(sub_id_1);
 
     contract
-        .with_account(deployer_wallet)
-        .unwrap()
         .methods()
         .mint(recipient_predicate_identity, Bits256(*sub_id_1), 1)
         .call()
@@ -134,3 +84,566 @@ async fn test_sanity() {
         .value;
     assert_eq!(total_supply_of_asset, Some(1));
 }
+
+#[tokio::test]
+async fn test_soulbound_predicate_rejects_every_spend() {
+    let ctx = TestContext::new().await;
+    let provider = ctx.deployer.try_provider().unwrap();
+    let recipient_wallet = &ctx.wallets[0];
+    let recipient: Address = recipient_wallet.address().into();
+    let thief = WalletUnlocked::new_random(Some(provider.clone()));
+    let predicate = ctx.predicate(recipient);
+
+    let badge = ctx
+        .mint_to_predicate(recipient, Bytes32::from([1u8; 32]))
+        .await;
+    // Spends out of the predicate pay their fee from its own coins, so fund it to make sure the
+    // transaction gets past coin selection and is rejected by predicate verification
+    ctx.fund_predicate(recipient, 1_000_000).await;
+
+    // The predicate allows no spend path at all, not even back to the wallet it is bound to
+    let cases = [
+        ("badge to another address", thief.address(), badge),
+        (
+            "badge back to the bound wallet",
+            recipient_wallet.address(),
+            badge,
+        ),
+        (
+            "base asset back to the bound wallet",
+            recipient_wallet.address(),
+            BASE_ASSET_ID,
+        ),
+    ];
+    for (case, to, asset) in cases {
+        // Pooled wallets are reused across tests, so balances are compared before and after
+        let predicate_balance = predicate.get_asset_balance(&asset).await.unwrap();
+        let to_balance = provider.get_asset_balance(to, asset).await.unwrap();
+
+        let result = predicate
+            .transfer(to, 1, asset, TxPolicies::default())
+            .await;
+        assert_predicate_rejected(result, case);
+
+        assert_eq!(
+            predicate.get_asset_balance(&asset).await.unwrap(),
+            predicate_balance,
+            "{case}"
+        );
+        assert_eq!(
+            provider.get_asset_balance(to, asset).await.unwrap(),
+            to_balance,
+            "{case}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_mint_reverts_for_non_owner() {
+    let ctx = TestContext::new().await;
+    let attacker = ctx.wallets[0].clone();
+    let recipient = ctx.predicate_identity(attacker.address().into());
+
+    let result = ctx
+        .contract
+        .with_account(attacker)
+        .unwrap()
+        .methods()
+        .mint(recipient, Bits256([1u8; 32]), 1)
+        .call()
+        .await;
+    assert_revert(result, "NotOwner");
+}
+
+#[tokio::test]
+async fn test_constructor_reverts_when_called_twice() {
+    let ctx = TestContext::new().await;
+
+    let result = ctx
+        .contract
+        .methods()
+        .constructor(ctx.deployer_identity())
+        .call()
+        .await;
+    assert_revert(result, "CannotReinitialized");
+}
+
+#[tokio::test]
+async fn test_mint_reverts_for_amount_greater_than_one() {
+    let ctx = TestContext::new().await;
+    let recipient = ctx.predicate_identity(ctx.wallets[0].address().into());
+
+    let result = ctx
+        .contract
+        .methods()
+        .mint(recipient, Bits256([1u8; 32]), 2)
+        .call()
+        .await;
+    assert_revert(result, "CannotMintMoreThanOneNFTWithSubId");
+}
+
+#[tokio::test]
+async fn test_mint_reverts_when_already_minted() {
+    let ctx = TestContext::new().await;
+    let recipient: Address = ctx.wallets[0].address().into();
+    let sub_id = Bytes32::from([1u8; 32]);
+
+    ctx.mint_to_predicate(recipient, sub_id).await;
+
+    let result = ctx
+        .contract
+        .methods()
+        .mint(ctx.predicate_identity(recipient), Bits256(*sub_id), 1)
+        .call()
+        .await;
+    assert_revert(result, "NFTAlreadyMinted");
+}
+
+#[tokio::test]
+async fn test_burn_reverts_for_more_than_held() {
+    let ctx = TestContext::new().await;
+    let sub_id = Bytes32::from([1u8; 32]);
+    let asset = ctx.asset_id(sub_id);
+
+    // Mint to the owner directly, as coins in a soulbound predicate can never be forwarded
+    ctx.contract
+        .methods()
+        .mint(ctx.deployer_identity(), Bits256(*sub_id), 1)
+        .call()
+        .await
+        .unwrap();
+
+    let result = ctx
+        .contract
+        .methods()
+        .burn(Bits256(*sub_id), 2)
+        .call_params(
+            CallParameters::default()
+                .with_amount(1)
+                .with_asset_id(asset),
+        )
+        .unwrap()
+        .call()
+        .await;
+    assert_revert(result, "NotEnoughCoins");
+}
+
+#[tokio::test]
+async fn test_burn_reverts_for_non_owner() {
+    let ctx = TestContext::new().await;
+    let attacker = ctx.wallets[0].clone();
+
+    let result = ctx
+        .contract
+        .with_account(attacker)
+        .unwrap()
+        .methods()
+        .burn(Bits256([1u8; 32]), 1)
+        .call()
+        .await;
+    assert_revert(result, "NotOwner");
+}
+
+proptest! {
+    // The VM derives the id of every asset a contract mints with `ContractIdExt::asset_id`, so
+    // `get_asset_id` has to agree with it for any contract and sub id
+    #[test]
+    fn prop_asset_id_matches_vm_derivation(
+        contract in any::<[u8; 32]>(),
+        sub_id in any::<[u8; 32]>(),
+    ) {
+        let contract = ContractId::from(contract);
+        let sub_id = Bytes32::from(sub_id);
+        prop_assert_eq!(get_asset_id(sub_id, contract), contract.asset_id(&sub_id));
+    }
+
+    // Fails if the `ADDRESS` configurable is not written into the predicate bytecode, since every
+    // owner would then get the same predicate
+    #[test]
+    fn prop_predicate_address_does_not_collide(a in any::<[u8; 32]>(), b in any::<[u8; 32]>()) {
+        prop_assume!(a != b);
+        prop_assert_ne!(predicate_address(Address::from(a)), predicate_address(Address::from(b)));
+    }
+}
+
+#[tokio::test]
+async fn test_predicate_address_matches_chain_for_generated_owners() {
+    let ctx = TestContext::new().await;
+
+    // Spending is too slow to run for every proptest case, so only check a small sample
+    let mut runner = TestRunner::deterministic();
+    let owners = proptest::collection::vec(any::<[u8; 32]>(), 3)
+        .new_tree(&mut runner)
+        .unwrap()
+        .current();
+
+    for owner in owners.into_iter().map(Address::from) {
+        ctx.fund_predicate(owner, 1_000).await;
+
+        // A coin can only be spent with predicate bytecode whose root is the coin's owner, and
+        // verification fails with `InvalidOwner` before the predicate runs otherwise. The
+        // predicate returning false shows that the locally derived address is that root.
+        let result = ctx
+            .predicate(owner)
+            .transfer(
+                ctx.deployer.address(),
+                1,
+                BASE_ASSET_ID,
+                TxPolicies::default(),
+            )
+            .await;
+        assert_predicate_rejected(result, &format!("spend for owner {owner}"));
+    }
+}
+
+#[tokio::test]
+async fn test_asset_id_matches_chain_for_generated_sub_ids() {
+    let ctx = TestContext::new().await;
+    let deployer: Address = ctx.deployer.address().into();
+
+    // Minting is too slow to run for every proptest case, so only check a small sample
+    let mut runner = TestRunner::deterministic();
+    let sub_ids = proptest::collection::vec(any::<[u8; 32]>(), 5)
+        .new_tree(&mut runner)
+        .unwrap()
+        .current();
+
+    for sub_id in sub_ids.into_iter().map(Bytes32::from) {
+        // Mint to the owner directly, so the recipient does not depend on any local derivation
+        let response = ctx
+            .contract
+            .methods()
+            .mint(ctx.deployer_identity(), Bits256(*sub_id), 1)
+            .call()
+            .await
+            .unwrap();
+
+        // The asset id the VM computed for the minted coin
+        let minted_asset = response
+            .receipts
+            .iter()
+            .find_map(|receipt| match receipt {
+                Receipt::TransferOut { to, asset_id, .. } if *to == deployer => Some(*asset_id),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(get_asset_id(sub_id, ctx.contract_id), minted_asset);
+
+        assert_eq!(
+            ctx.contract
+                .methods()
+                .total_supply(get_asset_id(sub_id, ctx.contract_id))
+                .simulate()
+                .await
+                .unwrap()
+                .value,
+            Some(1)
+        );
+    }
+}
+
+#[test]
+fn test_predicate_address_matches_golden_vectors() {
+    for vector in golden_vectors("predicate_addresses") {
+        let owner = Address::from_str(vector["owner"].as_str().unwrap()).unwrap();
+        let expected = Address::from_str(vector["predicate_address"].as_str().unwrap()).unwrap();
+
+        assert_eq!(predicate_address(owner), expected, "{vector}");
+    }
+}
+
+#[test]
+fn test_asset_id_matches_golden_vectors() {
+    for vector in golden_vectors("asset_ids") {
+        let contract = ContractId::from_str(vector["contract_id"].as_str().unwrap()).unwrap();
+        let sub_id = Bytes32::from_str(vector["sub_id"].as_str().unwrap()).unwrap();
+        let expected = AssetId::from_str(vector["asset_id"].as_str().unwrap()).unwrap();
+
+        assert_eq!(get_asset_id(sub_id, contract), expected, "{vector}");
+    }
+}
+
+#[tokio::test]
+#[ignore = "stress test, run with `cargo test -- --ignored`"]
+async fn test_stress_mint_5000_badges() {
+    const HOLDERS: usize = 500;
+    const BADGES_PER_HOLDER: usize = 10;
+
+    let ctx = TestContext::builder()
+        .amount_per_coin(100_000_000_000)
+        .build()
+        .await;
+    let provider = ctx.deployer.try_provider().unwrap();
+
+    let owners: Vec<Address> = (0..HOLDERS)
+        .map(|i| {
+            let mut owner = [0u8; 32];
+            owner[..8].copy_from_slice(&(i as u64 + 1).to_be_bytes());
+            Address::from(owner)
+        })
+        .collect();
+
+    let mints: Vec<(Identity, Bytes32)> = owners
+        .iter()
+        .enumerate()
+        .flat_map(|(i, owner)| {
+            (0..BADGES_PER_HOLDER).map(move |j| {
+                let mut sub_id = [0u8; 32];
+                sub_id[..8].copy_from_slice(&((i * BADGES_PER_HOLDER + j) as u64).to_be_bytes());
+                (
+                    Identity::Address(predicate_address(*owner)),
+                    Bytes32::from(sub_id),
+                )
+            })
+        })
+        .collect();
+
+    for chunk in mints.chunks_exact(MINTS_PER_MULTICALL) {
+        ctx.mint_multicall(chunk.try_into().unwrap()).await;
+    }
+
+    assert_eq!(
+        ctx.contract
+            .methods()
+            .total_assets()
+            .simulate()
+            .await
+            .unwrap()
+            .value,
+        (HOLDERS * BADGES_PER_HOLDER) as u64
+    );
+
+    for (_, sub_id) in mints.iter().step_by(97) {
+        assert_eq!(
+            ctx.contract
+                .methods()
+                .total_supply(ctx.asset_id(*sub_id))
+                .simulate()
+                .await
+                .unwrap()
+                .value,
+            Some(1)
+        );
+    }
+
+    for owner in &owners {
+        let balances = provider
+            .get_balances(&predicate_address(*owner).into())
+            .await
+            .unwrap();
+        assert_eq!(balances.len(), BADGES_PER_HOLDER);
+        assert!(balances.values().all(|amount| *amount == 1));
+    }
+}
+
+#[tokio::test]
+async fn test_independent_contracts_do_not_share_state() {
+    let ctx = TestContext::new().await;
+    let other_owner = &ctx.wallets[0];
+    let (other_contract, other_contract_id) =
+        deploy_contract_with_salt(other_owner, unique_salt()).await;
+
+    let recipient: Address = ctx.deployer.address().into();
+    let recipient_identity = ctx.predicate_identity(recipient);
+    let sub_id = Bytes32::from([1u8; 32]);
+    let asset = ctx.asset_id(sub_id);
+    let other_asset = get_asset_id(sub_id, other_contract_id);
+
+    assert_ne!(ctx.contract_id, other_contract_id);
+    assert_ne!(asset, other_asset);
+
+    // The same sub id can be minted on both contracts
+    ctx.mint_to_predicate(recipient, sub_id).await;
+    other_contract
+        .methods()
+        .mint(recipient_identity.clone(), Bits256(*sub_id), 1)
+        .call()
+        .await
+        .unwrap();
+
+    for (contract, own_asset, foreign_asset) in [
+        (&ctx.contract, asset, other_asset),
+        (&other_contract, other_asset, asset),
+    ] {
+        assert_eq!(
+            contract
+                .methods()
+                .total_assets()
+                .simulate()
+                .await
+                .unwrap()
+                .value,
+            1
+        );
+        assert_eq!(
+            contract
+                .methods()
+                .total_supply(own_asset)
+                .simulate()
+                .await
+                .unwrap()
+                .value,
+            Some(1)
+        );
+        assert_eq!(
+            contract
+                .methods()
+                .total_supply(foreign_asset)
+                .simulate()
+                .await
+                .unwrap()
+                .value,
+            None
+        );
+    }
+
+    // Predicates are bound to wallets, not contracts, so one predicate holds both badges
+    let predicate = ctx.predicate(recipient);
+    assert_eq!(predicate.get_asset_balance(&asset).await.unwrap(), 1);
+    assert_eq!(predicate.get_asset_balance(&other_asset).await.unwrap(), 1);
+
+    // Ownership does not leak across instances either
+    let result = ctx
+        .contract
+        .with_account(other_owner.clone())
+        .unwrap()
+        .methods()
+        .mint(recipient_identity.clone(), Bits256([2u8; 32]), 1)
+        .call()
+        .await;
+    assert_revert(result, "NotOwner");
+
+    let result = other_contract
+        .with_account(ctx.deployer.clone())
+        .unwrap()
+        .methods()
+        .mint(recipient_identity, Bits256([2u8; 32]), 1)
+        .call()
+        .await;
+    assert_revert(result, "NotOwner");
+}
+
+// This snapshots the storage slots forc emits at compile time, i.e. the initial values of the
+// `storage` block. It catches layout changes such as a renamed or reordered field, but not the
+// storage written by a scenario: the node's API has no query for a contract's storage slots, so
+// the state after a scenario is snapshotted through the ABI instead, by
+// `test_state_snapshot_after_mint_metadata_and_burn`.
+#[test]
+fn test_storage_layout_snapshot() {
+    let storage_slots = std::fs::read_to_string(CONTRACT_STORAGE_SLOTS).unwrap();
+    assert_snapshot("storage_slots", &storage_slots);
+}
+
+#[tokio::test]
+async fn test_state_snapshot_after_mint_metadata_and_burn() {
+    let ctx = TestContext::new().await;
+    let recipient: Address = ctx.wallets[0].address().into();
+    let soulbound_sub_id = Bytes32::from([1u8; 32]);
+    let burned_sub_id = Bytes32::from([2u8; 32]);
+
+    ctx.mint_to_predicate(recipient, soulbound_sub_id).await;
+    ctx.contract
+        .methods()
+        .set_metadata(
+            ctx.asset_id(soulbound_sub_id),
+            "uri".to_string(),
+            Metadata::String("https://learnweb3.io/badges/1".to_string()),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    ctx.contract
+        .methods()
+        .mint(ctx.deployer_identity(), Bits256(*burned_sub_id), 1)
+        .call()
+        .await
+        .unwrap();
+    ctx.contract
+        .methods()
+        .burn(Bits256(*burned_sub_id), 1)
+        .call_params(
+            CallParameters::default()
+                .with_amount(1)
+                .with_asset_id(ctx.asset_id(burned_sub_id)),
+        )
+        .unwrap()
+        .call()
+        .await
+        .unwrap();
+
+    let snapshot = ctx
+        .state_snapshot(&[soulbound_sub_id, burned_sub_id], &["uri"])
+        .await;
+    assert_snapshot("state_after_mint_metadata_and_burn", &snapshot);
+}
+
+#[tokio::test]
+async fn test_fund_and_mint_script() {
+    let ctx = TestContext::new().await;
+    let recipient: Address = ctx.wallets[0].address().into();
+    let predicate = ctx.predicate(recipient);
+    let balance = predicate.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+
+    let asset = ctx
+        .fund_and_mint_to_predicate(recipient, Bytes32::from([1u8; 32]), 1_000)
+        .await;
+
+    assert_eq!(predicate.get_asset_balance(&asset).await.unwrap(), 1);
+    assert_eq!(
+        predicate.get_asset_balance(&BASE_ASSET_ID).await.unwrap(),
+        balance + 1_000
+    );
+    assert_eq!(
+        ctx.contract
+            .methods()
+            .total_supply(asset)
+            .simulate()
+            .await
+            .unwrap()
+            .value,
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn test_fund_and_mint_script_spends_several_small_coins() {
+    // No single coin covers the funded amount, so the script needs several base asset inputs
+    let ctx = TestContext::builder()
+        .coins_per_wallet(20)
+        .amount_per_coin(100)
+        .build()
+        .await;
+    let recipient: Address = ctx.wallets[0].address().into();
+    let predicate = ctx.predicate(recipient);
+
+    let asset = ctx
+        .fund_and_mint_to_predicate(recipient, Bytes32::from([1u8; 32]), 1_000)
+        .await;
+
+    assert_eq!(predicate.get_asset_balance(&asset).await.unwrap(), 1);
+    assert_eq!(
+        predicate.get_asset_balance(&BASE_ASSET_ID).await.unwrap(),
+        1_000
+    );
+}
+
+#[tokio::test]
+async fn test_fund_and_mint_script_reverts_for_non_owner() {
+    let ctx = TestContext::new().await;
+    let attacker = ctx.wallets[0].clone();
+    let script = FundAndMintScript::new(attacker.clone(), FUND_AND_MINT_SCRIPT_BINARY);
+
+    let result = script
+        .main(
+            Bits256(*ctx.contract_id),
+            predicate_address(attacker.address().into()),
+            Bits256([1u8; 32]),
+            1_000,
+        )
+        .with_contracts(&[&ctx.contract])
+        .append_variable_outputs(2)
+        .call()
+        .await;
+    assert_revert(result, "NotOwner");
+}
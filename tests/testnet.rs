@@ -0,0 +1,189 @@
+//! Reduced test suite against a public testnet, enabled with the `testnet-tests` feature.
+//!
+//! Requires `TESTNET_PRIVATE_KEY` to hold the private key of a funded wallet. The node defaults
+//! to the public beta-5 testnet and can be overridden with `TESTNET_NODE_URL`. The tests all sign
+//! with that wallet, so they take [TESTNET_WALLET_LOCK] and run one at a time.
+//!
+//! `test_testnet_record_derivation_vectors` is ignored by default and rewrites the golden vectors
+//! in `tests/fixtures/derivation_vectors.json`.
+
+mod common;
+
+use common::*;
+use fuel_crypto::SecretKey;
+use fuels::{
+    prelude::*,
+    tx::{Bytes32, Receipt},
+    types::{Bits256, Identity},
+};
+use serde_json::{json, Value};
+use std::{
+    env, fs,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Mutex;
+
+const DEFAULT_NODE_URL: &str = "beta-5.fuel.network";
+const DERIVATION_VECTORS_PATH: &str = "./tests/fixtures/derivation_vectors.json";
+
+/// Owners whose predicate addresses are recorded as golden vectors.
+const VECTOR_OWNERS: [[u8; 32]; 3] = [[0x01; 32], [0x02; 32], [0xab; 32]];
+
+/// Sub ids whose asset ids are recorded as golden vectors.
+const VECTOR_SUB_IDS: [[u8; 32]; 3] = [[0x00; 32], [0x01; 32], [0xff; 32]];
+
+/// Held by each test while it uses the testnet wallet. Transactions sent in parallel from the
+/// same wallet could select the same coins, and all but one of them would be rejected.
+static TESTNET_WALLET_LOCK: Mutex<()> = Mutex::const_new(());
+
+async fn get_testnet_wallet() -> WalletUnlocked {
+    let node_url = env::var("TESTNET_NODE_URL").unwrap_or_else(|_| DEFAULT_NODE_URL.to_string());
+    let provider = Provider::connect(node_url).await.unwrap();
+
+    let private_key = env::var("TESTNET_PRIVATE_KEY").expect("TESTNET_PRIVATE_KEY must be set");
+    let secret_key = SecretKey::from_str(&private_key).unwrap();
+
+    WalletUnlocked::new_from_private_key(secret_key, Some(provider))
+}
+
+fn tx_policies() -> TxPolicies {
+    // The public testnet rejects transactions below its minimum gas price
+    TxPolicies::default().with_gas_price(1)
+}
+
+/// Returns a salt that differs on every run, so each run deploys a fresh contract.
+fn run_salt() -> [u8; 32] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+
+    let mut salt = [0u8; 32];
+    salt[..16].copy_from_slice(&nanos.to_be_bytes());
+    salt
+}
+
+#[tokio::test]
+async fn test_testnet_deploy_mint_burn() {
+    let _lock = TESTNET_WALLET_LOCK.lock().await;
+    let wallet = get_testnet_wallet().await;
+    let owner_identity = Identity::Address(wallet.address().into());
+
+    let (contract, contract_id) =
+        deploy_contract_with_policies(&wallet, run_salt(), tx_policies()).await;
+    assert_eq!(
+        contract.methods().owner().simulate().await.unwrap().value,
+        State::Initialized(owner_identity.clone())
+    );
+
+    // Mint to the owner directly, as coins in a soulbound predicate can never be burned
+    let sub_id = Bytes32::from([1u8; 32]);
+    let asset = get_asset_id(sub_id, contract_id);
+    contract
+        .methods()
+        .mint(owner_identity, Bits256(*sub_id), 1)
+        .with_tx_policies(tx_policies())
+        .call()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        contract
+            .methods()
+            .total_supply(asset)
+            .simulate()
+            .await
+            .unwrap()
+            .value,
+        Some(1)
+    );
+    assert_eq!(wallet.get_asset_balance(&asset).await.unwrap(), 1);
+
+    contract
+        .methods()
+        .burn(Bits256(*sub_id), 1)
+        .call_params(
+            CallParameters::default()
+                .with_amount(1)
+                .with_asset_id(asset),
+        )
+        .unwrap()
+        .with_tx_policies(tx_policies())
+        .call()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        contract
+            .methods()
+            .total_supply(asset)
+            .simulate()
+            .await
+            .unwrap()
+            .value,
+        Some(0)
+    );
+    assert_eq!(wallet.get_asset_balance(&asset).await.unwrap(), 0);
+}
+
+#[tokio::test]
+#[ignore = "rewrites tests/fixtures/derivation_vectors.json, run explicitly to record new vectors"]
+async fn test_testnet_record_derivation_vectors() {
+    let _lock = TESTNET_WALLET_LOCK.lock().await;
+    let wallet = get_testnet_wallet().await;
+    let owner: Address = wallet.address().into();
+
+    let (contract, contract_id) =
+        deploy_contract_with_policies(&wallet, run_salt(), tx_policies()).await;
+
+    let mut asset_ids = vec![];
+    for sub_id in VECTOR_SUB_IDS.map(Bytes32::from) {
+        let response = contract
+            .methods()
+            .mint(Identity::Address(owner), Bits256(*sub_id), 1)
+            .with_tx_policies(tx_policies())
+            .call()
+            .await
+            .unwrap();
+
+        // Record the asset id of the coin the network minted, not the local derivation
+        let asset_id = response
+            .receipts
+            .iter()
+            .find_map(|receipt| match receipt {
+                Receipt::TransferOut { to, asset_id, .. } if *to == owner => Some(*asset_id),
+                _ => None,
+            })
+            .unwrap();
+
+        asset_ids.push(json!({
+            "contract_id": format!("{contract_id:#x}"),
+            "sub_id": format!("{sub_id:#x}"),
+            "asset_id": format!("{asset_id:#x}"),
+        }));
+    }
+
+    // Computed locally from the predicate bytecode of this build and the configurables encoding of
+    // fuels-rs, nothing here comes from the deployment
+    let predicate_addresses: Vec<Value> = VECTOR_OWNERS
+        .map(Address::from)
+        .iter()
+        .map(|owner| {
+            json!({
+                "owner": format!("{owner:#x}"),
+                "predicate_address": format!("{:#x}", predicate_address(*owner)),
+            })
+        })
+        .collect();
+
+    let vectors = json!({
+        "predicate_addresses": predicate_addresses,
+        "asset_ids": asset_ids,
+    });
+    fs::write(
+        DERIVATION_VECTORS_PATH,
+        serde_json::to_string_pretty(&vectors).unwrap() + "\n",
+    )
+    .unwrap();
+}